@@ -1,5 +1,7 @@
 use pyo3::prelude::*;
 use pyo3::exceptions::PyValueError;
+use rayon::prelude::*;
+use rustc_hash::FxHashMap;
 use std::collections::HashMap;
 
 /// Find the cheapest price in a list of prices.
@@ -21,6 +23,81 @@ fn predict_price(item: String, location: String) -> f64 {
     400.0 + hash
 }
 
+/// Great-circle distance in kilometers between two (lat, long) points given in degrees,
+/// via the spherical law of cosines. Identical points return exactly 0.0, and the `acos`
+/// argument is clamped against floating-point drift so it never yields NaN.
+fn great_circle_km(a: (f64, f64), b: (f64, f64)) -> f64 {
+    const EARTH_RADIUS_KM: f64 = 6371.0;
+
+    if a == b {
+        return 0.0;
+    }
+
+    let (lat1, long1) = (a.0.to_radians(), a.1.to_radians());
+    let (lat2, long2) = (b.0.to_radians(), b.1.to_radians());
+
+    let central_angle_cos =
+        lat1.sin() * lat2.sin() + lat1.cos() * lat2.cos() * (long2 - long1).cos();
+
+    EARTH_RADIUS_KM * central_angle_cos.clamp(-1.0, 1.0).acos()
+}
+
+/// Predict a delivery-inclusive price from the great-circle distance between buyer and
+/// vendor, as a location-sensitive alternative to the string-length `predict_price`
+/// placeholder (which is still exported): a flat base price plus a per-kilometer delivery rate.
+#[pyfunction]
+fn predict_delivery_price(
+    buyer: (f64, f64),
+    vendor: (f64, f64),
+    base_price: f64,
+    per_km_rate: f64,
+) -> f64 {
+    let distance_km = great_circle_km(buyer, vendor);
+    base_price + per_km_rate * distance_km
+}
+
+/// Cheapest vendor reachable within a travel budget, weighting distance against price rather
+/// than just picking the global minimum: vendors further than `max_cost` km are dropped
+/// entirely, and the rest are ranked by `price / weight` where `weight` decays toward 0 with
+/// distance (Gaussian, scaled by `decay`) so a cheap-but-far vendor can lose to a
+/// slightly-pricier-but-near one.
+#[pyfunction]
+fn accessible_best_price(
+    buyer: (f64, f64),
+    vendors: Vec<((f64, f64), f64)>,
+    max_cost: f64,
+    decay: f64,
+) -> PyResult<HashMap<String, f64>> {
+    let mut best: Option<HashMap<String, f64>> = None;
+    let mut best_effective_cost = f64::INFINITY;
+
+    for (coords, price) in vendors {
+        let distance_km = great_circle_km(buyer, coords);
+        if distance_km > max_cost {
+            continue;
+        }
+
+        let weight = (-(distance_km / decay).powi(2)).exp();
+        if weight <= 0.0 {
+            continue;
+        }
+
+        let effective_cost = price / weight;
+        if effective_cost < best_effective_cost {
+            best_effective_cost = effective_cost;
+            let mut entry = HashMap::new();
+            entry.insert("lat".to_string(), coords.0);
+            entry.insert("long".to_string(), coords.1);
+            entry.insert("price".to_string(), price);
+            entry.insert("distance_km".to_string(), distance_km);
+            entry.insert("weight".to_string(), weight);
+            best = Some(entry);
+        }
+    }
+
+    best.ok_or_else(|| PyValueError::new_err("No vendor is reachable within max_cost"))
+}
+
 /// Aggregate statistics (mean, min, max, total)
 #[pyfunction]
 fn aggregate_stats(prices: Vec<f64>, quantities: Vec<i64>) -> PyResult<HashMap<String, f64>> {
@@ -75,12 +152,363 @@ fn weighted_average_price(prices: Vec<f64>, quantities: Vec<i64>) -> PyResult<f6
     Ok(weighted_sum / total_qty as f64)
 }
 
+/// Cumulative volume-weighted average price at each index, for charting a price-trend line:
+/// `result[i]` is the VWAP of all observations `0..=i`. While cumulative quantity is still
+/// zero, emits the plain price instead of dividing by zero.
+#[pyfunction]
+fn running_vwap(prices: Vec<f64>, quantities: Vec<i64>) -> PyResult<Vec<f64>> {
+    if prices.len() != quantities.len() {
+        return Err(PyValueError::new_err(
+            "Prices and quantities must have the same length",
+        ));
+    }
+
+    let mut result = Vec::with_capacity(prices.len());
+    let mut numerator = 0.0;
+    let mut denominator = 0.0;
+
+    for (&price, &qty) in prices.iter().zip(quantities.iter()) {
+        numerator += price * qty as f64;
+        denominator += qty as f64;
+
+        if denominator == 0.0 {
+            result.push(price);
+        } else {
+            result.push(numerator / denominator);
+        }
+    }
+
+    Ok(result)
+}
+
+/// Format a float as a Naira amount, e.g. `1234567.891` -> `"₦1,234,567.89"`.
+/// Groups the integer part into thousands and always shows two decimal (kobo) places.
+#[pyfunction]
+fn format_naira(amount: f64) -> String {
+    let rounded = (amount.abs() * 100.0).round() / 100.0;
+
+    let whole = rounded.trunc() as i64;
+    let kobo = ((rounded - whole as f64) * 100.0).round() as i64;
+    let negative = amount < 0.0 && (whole != 0 || kobo != 0);
+
+    let whole_digits = whole.to_string();
+    let mut grouped = String::with_capacity(whole_digits.len() + whole_digits.len() / 3);
+    for (i, ch) in whole_digits.chars().enumerate() {
+        if i > 0 && (whole_digits.len() - i) % 3 == 0 {
+            grouped.push(',');
+        }
+        grouped.push(ch);
+    }
+
+    format!(
+        "{}₦{}.{:02}",
+        if negative { "-" } else { "" },
+        grouped,
+        kobo
+    )
+}
+
+/// Weighted median price across multiple vendor feeds, robust to outliers and bad feeds.
+#[pyfunction]
+fn consensus_price(feeds: Vec<(f64, f64)>) -> PyResult<f64> {
+    let mut pairs: Vec<(f64, f64)> = feeds
+        .into_iter()
+        .filter(|&(weight, price)| weight > 0.0 && price.is_finite() && price >= 0.0)
+        .collect();
+
+    if pairs.is_empty() {
+        return Err(PyValueError::new_err(
+            "No valid (weight, price) feeds remain after filtering",
+        ));
+    }
+
+    pairs.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap());
+
+    let total: f64 = pairs.iter().map(|&(weight, _)| weight).sum();
+    let half = total / 2.0;
+
+    let mut cumulative = 0.0;
+    for (i, &(weight, price)) in pairs.iter().enumerate() {
+        cumulative += weight;
+        if cumulative == half {
+            if let Some(&(_, next_price)) = pairs.get(i + 1) {
+                return Ok((price + next_price) / 2.0);
+            }
+            return Ok(price);
+        }
+        if cumulative > half {
+            return Ok(price);
+        }
+    }
+
+    Ok(pairs.last().unwrap().1)
+}
+
+/// A single weighted point in a t-digest, representing the mean of all values merged into it.
+struct Centroid {
+    mean: f64,
+    count: f64,
+}
+
+/// Streaming quantile sketch: keeps a bounded set of weighted centroids instead of sorting
+/// every observation, with small centroids near the tails (q near 0 or 1) for accurate
+/// percentiles there and larger centroids in the middle where precision matters less.
+struct TDigest {
+    centroids: Vec<Centroid>,
+    total: f64,
+    compression: f64,
+}
+
+impl TDigest {
+    fn new(compression: f64) -> Self {
+        TDigest {
+            centroids: Vec::new(),
+            total: 0.0,
+            compression,
+        }
+    }
+
+    /// Max count a centroid sitting at cumulative quantile `q` is allowed to hold before a
+    /// new value must start its own centroid instead of merging in.
+    fn max_count_at(&self, q: f64) -> f64 {
+        (4.0 * self.total * q * (1.0 - q) / self.compression).max(1.0)
+    }
+
+    fn add(&mut self, value: f64, weight: f64) {
+        if weight <= 0.0 {
+            return;
+        }
+
+        self.total += weight;
+
+        let insert_at = self
+            .centroids
+            .partition_point(|c| c.mean < value);
+
+        let mut cumulative: f64 = self.centroids[..insert_at].iter().map(|c| c.count).sum();
+
+        let mut candidate = None;
+        for idx in [insert_at.checked_sub(1), Some(insert_at)] {
+            if let Some(idx) = idx {
+                if let Some(c) = self.centroids.get(idx) {
+                    let q = (cumulative + c.count / 2.0) / self.total;
+                    let room = self.max_count_at(q) - c.count;
+                    if room >= weight {
+                        let best = candidate.map(|(_, r): (usize, f64)| r);
+                        if best.map_or(true, |r| room < r) {
+                            candidate = Some((idx, room));
+                        }
+                    }
+                }
+                if idx == insert_at.saturating_sub(1) {
+                    cumulative += self.centroids.get(idx).map_or(0.0, |c| c.count);
+                }
+            }
+        }
+
+        if let Some((idx, _)) = candidate {
+            let c = &mut self.centroids[idx];
+            let new_count = c.count + weight;
+            c.mean += (value - c.mean) * weight / new_count;
+            c.count = new_count;
+        } else {
+            self.centroids.insert(insert_at, Centroid { mean: value, count: weight });
+        }
+
+        if self.centroids.len() as f64 > 10.0 * self.compression {
+            self.compress();
+        }
+    }
+
+    /// Re-merge neighbouring centroids that now fit within their size bound, bringing the
+    /// digest back down toward ~`compression` centroids.
+    fn compress(&mut self) {
+        let old = std::mem::take(&mut self.centroids);
+        self.centroids = Vec::with_capacity(old.len());
+        let mut cumulative = 0.0;
+
+        for c in old {
+            if let Some(last_idx) = self.centroids.len().checked_sub(1) {
+                let last_count = self.centroids[last_idx].count;
+                let q = (cumulative + last_count / 2.0) / self.total;
+                let bound = self.max_count_at(q);
+                if last_count + c.count <= bound {
+                    let last = &mut self.centroids[last_idx];
+                    let new_count = last_count + c.count;
+                    last.mean += (c.mean - last.mean) * c.count / new_count;
+                    last.count = new_count;
+                    cumulative += c.count;
+                    continue;
+                }
+            }
+            cumulative += c.count;
+            self.centroids.push(c);
+        }
+    }
+
+    /// Interpolated value at quantile `p` (0.0..=1.0).
+    fn quantile(&self, p: f64) -> f64 {
+        if self.centroids.is_empty() {
+            return 0.0;
+        }
+        if self.centroids.len() == 1 {
+            return self.centroids[0].mean;
+        }
+
+        let target = p * self.total;
+        let mut cumulative = 0.0;
+
+        for i in 0..self.centroids.len() {
+            let c = &self.centroids[i];
+            let next_cumulative = cumulative + c.count;
+            if next_cumulative >= target || i == self.centroids.len() - 1 {
+                let prev = if i == 0 { c } else { &self.centroids[i - 1] };
+                let next = if i == self.centroids.len() - 1 { c } else { &self.centroids[i + 1] };
+                let span = next_cumulative - cumulative;
+                if span <= 0.0 {
+                    return c.mean;
+                }
+                let frac = ((target - cumulative) / span).clamp(0.0, 1.0);
+                return prev.mean + (next.mean - prev.mean) * frac;
+            }
+            cumulative = next_cumulative;
+        }
+
+        self.centroids.last().unwrap().mean
+    }
+}
+
+/// Percentile/quantile breakdown of a price series (e.g. p50, p90, p95), computed with a
+/// streaming t-digest so it scales to millions of scraped prices without sorting them all.
+#[pyfunction]
+fn aggregate_stats_percentiles(
+    prices: Vec<f64>,
+    quantities: Vec<i64>,
+    percentiles: Vec<f64>,
+) -> PyResult<HashMap<String, f64>> {
+    if prices.len() != quantities.len() {
+        return Err(PyValueError::new_err(
+            "Prices and quantities must have the same length",
+        ));
+    }
+
+    let mut digest = TDigest::new(100.0);
+    for &price in &prices {
+        digest.add(price, 1.0);
+    }
+    digest.compress();
+
+    let mut result = HashMap::new();
+    for &p in &percentiles {
+        let key = format!("p{}", (p * 100.0).round() as i64);
+        result.insert(key, digest.quantile(p));
+    }
+
+    Ok(result)
+}
+
+/// Running min/mean/max/total for a single item, accumulated in one pass.
+struct ItemAccumulator {
+    count: f64,
+    sum: f64,
+    min: f64,
+    max: f64,
+    qty: f64,
+}
+
+impl ItemAccumulator {
+    fn new() -> Self {
+        ItemAccumulator {
+            count: 0.0,
+            sum: 0.0,
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+            qty: 0.0,
+        }
+    }
+
+    fn push(&mut self, price: f64, qty: i64) {
+        self.count += 1.0;
+        self.sum += price;
+        self.min = self.min.min(price);
+        self.max = self.max.max(price);
+        self.qty += qty as f64;
+    }
+
+    fn merge(&mut self, other: &ItemAccumulator) {
+        self.count += other.count;
+        self.sum += other.sum;
+        self.min = self.min.min(other.min);
+        self.max = self.max.max(other.max);
+        self.qty += other.qty;
+    }
+}
+
+/// Per-item min/mean/max/total over millions of scraped `(item, price, quantity)` rows in a
+/// single Rust pass: rows are split into chunks processed in parallel with rayon, each chunk
+/// building its own local hashmap of accumulators, then the partial maps are folded together.
+#[pyfunction]
+fn aggregate_by_item(
+    items: Vec<String>,
+    prices: Vec<f64>,
+    quantities: Vec<i64>,
+) -> PyResult<HashMap<String, HashMap<String, f64>>> {
+    if items.len() != prices.len() || items.len() != quantities.len() {
+        return Err(PyValueError::new_err(
+            "items, prices and quantities must have the same length",
+        ));
+    }
+
+    let rows: Vec<(&str, f64, i64)> = items
+        .iter()
+        .map(String::as_str)
+        .zip(prices.iter().copied())
+        .zip(quantities.iter().copied())
+        .map(|((item, price), qty)| (item, price, qty))
+        .collect();
+
+    let merged: FxHashMap<&str, ItemAccumulator> = rows
+        .par_chunks(10_000.max(rows.len() / rayon::current_num_threads().max(1)))
+        .map(|chunk| {
+            let mut local: FxHashMap<&str, ItemAccumulator> = FxHashMap::default();
+            for &(item, price, qty) in chunk {
+                local.entry(item).or_insert_with(ItemAccumulator::new).push(price, qty);
+            }
+            local
+        })
+        .reduce(FxHashMap::default, |mut acc, partial| {
+            for (item, stats) in partial {
+                acc.entry(item).or_insert_with(ItemAccumulator::new).merge(&stats);
+            }
+            acc
+        });
+
+    let mut result = HashMap::with_capacity(merged.len());
+    for (item, acc) in merged {
+        let mut stats = HashMap::new();
+        stats.insert("min_val".to_string(), acc.min);
+        stats.insert("mean".to_string(), acc.sum / acc.count);
+        stats.insert("max_val".to_string(), acc.max);
+        stats.insert("total_qty".to_string(), acc.qty);
+        result.insert(item.to_string(), stats);
+    }
+
+    Ok(result)
+}
+
 #[pymodule]
 fn rust_engine(py: Python, module: &Bound<'_, PyModule>) -> PyResult<()> {
     module.add_function(wrap_pyfunction!(cheapest, module)?)?;
     module.add_function(wrap_pyfunction!(savings, module)?)?;
     module.add_function(wrap_pyfunction!(predict_price, module)?)?;
+    module.add_function(wrap_pyfunction!(predict_delivery_price, module)?)?;
     module.add_function(wrap_pyfunction!(aggregate_stats, module)?)?;
     module.add_function(wrap_pyfunction!(weighted_average_price, module)?)?;
+    module.add_function(wrap_pyfunction!(consensus_price, module)?)?;
+    module.add_function(wrap_pyfunction!(format_naira, module)?)?;
+    module.add_function(wrap_pyfunction!(aggregate_stats_percentiles, module)?)?;
+    module.add_function(wrap_pyfunction!(aggregate_by_item, module)?)?;
+    module.add_function(wrap_pyfunction!(running_vwap, module)?)?;
+    module.add_function(wrap_pyfunction!(accessible_best_price, module)?)?;
     Ok(())
 }